@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+use bytes::Bytes;
+
+use crate::component::NetworkNode;
+use crate::AsyncChannel;
+
+/// Reserved first byte on every packet a typed subsystem (RPC, streaming,
+/// typed codecs) sends through a [`NetworkNode`], since several of them can
+/// share the same node's receive channel. Without this,
+/// each subsystem's dispatch system would drain `recv_message_channel` to
+/// completion on its own, racing the others for the same FIFO and
+/// misparsing whatever frames it stole from them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ChannelKind {
+    Rpc = 0,
+    Stream = 1,
+    Typed = 2,
+}
+
+impl ChannelKind {
+    pub fn to_byte(self) -> u8 {
+        self as u8
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Rpc),
+            1 => Some(Self::Stream),
+            2 => Some(Self::Typed),
+            _ => None,
+        }
+    }
+}
+
+/// Holds the routed, per-[`ChannelKind`] inbound queues for a [`NetworkNode`].
+/// [`demux_recv_channel`] is the only system allowed to read directly from
+/// `NetworkNode::recv_channel`; `RpcNode`, `StreamingNode`, and
+/// `TypedDecoder<T>` read their frames back out from here instead.
+#[derive(Component, Default)]
+pub struct ChannelInbox {
+    pub(crate) rpc: AsyncChannel<Bytes>,
+    pub(crate) stream: AsyncChannel<Bytes>,
+    pub(crate) typed: AsyncChannel<Bytes>,
+}
+
+pub struct ChannelDemuxPlugin;
+
+impl Plugin for ChannelDemuxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, demux_recv_channel);
+    }
+}
+
+/// Drains each node's raw `recv_message_channel` once per tick and routes
+/// every packet, by its leading [`ChannelKind`] byte, into the matching
+/// lane of that node's [`ChannelInbox`].
+///
+/// `pub(crate)` so `RpcPlugin`/`NetworkStreamPlugin`/`register_decoder`
+/// can each order their `ChannelInbox`-insertion system ahead of this one,
+/// guaranteeing the inbox exists before anything tries to demux into it.
+pub(crate) fn demux_recv_channel(q_nodes: Query<(&NetworkNode, &ChannelInbox)>) {
+    for (net, inbox) in q_nodes.iter() {
+        while let Ok(Some(packet)) = net.recv_channel().receiver.try_recv() {
+            if packet.bytes.is_empty() {
+                debug!("dropped empty packet with no channel kind byte");
+                continue;
+            }
+            let kind_byte = packet.bytes[0];
+            let rest = packet.bytes.slice(1..);
+            match ChannelKind::from_byte(kind_byte) {
+                Some(ChannelKind::Rpc) => {
+                    let _ = inbox.rpc.sender.try_send(rest);
+                }
+                Some(ChannelKind::Stream) => {
+                    let _ = inbox.stream.sender.try_send(rest);
+                }
+                Some(ChannelKind::Typed) => {
+                    let _ = inbox.typed.sender.try_send(rest);
+                }
+                None => debug!("dropped packet with unknown channel kind {kind_byte}"),
+            }
+        }
+    }
+}
@@ -7,7 +7,7 @@ use std::{
 use bevy::prelude::Component;
 use bytes::Bytes;
 
-use crate::{error::NetworkError, AsyncChannel, NetworkRawPacket};
+use crate::{error::NetworkError, framing::Framing, AsyncChannel, NetworkRawPacket};
 
 
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq)]
@@ -17,6 +17,7 @@ pub enum NetworkProtocol {
     SSL,
     WS,
     WSS,
+    QUIC,
 }
 
 impl Display for NetworkProtocol {
@@ -30,6 +31,7 @@ impl Display for NetworkProtocol {
                 NetworkProtocol::SSL => "ssl",
                 NetworkProtocol::WS => "ws",
                 NetworkProtocol::WSS => "wss",
+                NetworkProtocol::QUIC => "quic",
             }
         )
     }
@@ -45,6 +47,15 @@ pub struct NetworkNode {
     error_channel: AsyncChannel<NetworkError>,
     /// A flag to cancel the node
     pub cancel_flag: Arc<AtomicBool>,
+    /// Set by [`stop_graceful`](Self::stop_graceful) to stop accepting new
+    /// work while letting queued traffic drain before `cancel_flag` is set.
+    pub graceful_flag: Arc<AtomicBool>,
+    /// Set by a transport's recv loop when the peer has actually gone away
+    /// (EOF or a read error), as opposed to `running`/`graceful_flag` which
+    /// only reflect this side's own intent to stop. Transports that spawn a
+    /// child entity per connection (e.g. `TcpServerNode`) watch this to know
+    /// when to despawn that child.
+    pub disconnected_flag: Arc<AtomicBool>,
     /// Whether the node is running or not
     pub running: bool,
     /// Local address
@@ -52,6 +63,8 @@ pub struct NetworkNode {
     pub peer_addr: Option<SocketAddr>,
     pub max_packet_size: usize,
     pub auto_start: bool,
+    /// How stream transports reconstruct message boundaries from raw bytes.
+    pub framing: Framing,
     protocol: NetworkProtocol,
 }
 
@@ -66,14 +79,23 @@ impl NetworkNode {
             send_message_channel: AsyncChannel::new(),
             error_channel: AsyncChannel::new(),
             cancel_flag: Arc::new(AtomicBool::new(false)),
+            graceful_flag: Arc::new(AtomicBool::new(false)),
+            disconnected_flag: Arc::new(AtomicBool::new(false)),
             running: false,
             local_addr,
             peer_addr,
             max_packet_size: 65535,
             auto_start: true,
+            framing: Framing::default(),
             protocol,
         }
     }
+
+    /// Builds a [`FrameDecoder`](crate::framing::FrameDecoder) for this node's
+    /// [`framing`](Self::framing) configuration, sized to `max_packet_size`.
+    pub fn frame_decoder(&self) -> Box<dyn crate::framing::FrameDecoder> {
+        self.framing.decoder(self.max_packet_size)
+    }
     pub fn start(&mut self) {
         self.cancel_flag
             .store(false, std::sync::atomic::Ordering::Relaxed);
@@ -86,6 +108,22 @@ impl NetworkNode {
         self.running = false;
     }
 
+    /// Stops accepting/initiating new work and marks the node as no longer
+    /// `running`, but — unlike [`stop`](Self::stop) — leaves `cancel_flag`
+    /// unset so the send loop keeps draining `send_message_channel` until
+    /// it's empty, then flushes and closes. Layers built on top (e.g. the
+    /// RPC subsystem) must treat `cancel_flag`, not `running`, as "the
+    /// connection is gone"; `running == false` here only means "stop
+    /// starting new things", not "abandon what's in flight". Use this on
+    /// app exit or a planned disconnect, where dropping the last few
+    /// packets would corrupt protocol state; use [`stop`](Self::stop) when
+    /// that doesn't matter.
+    pub fn stop_graceful(&mut self) {
+        self.graceful_flag
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+        self.running = false;
+    }
+
     pub fn send(&self, bytes: &[u8]) {
         self.send_message_channel
             .sender
@@ -0,0 +1,143 @@
+use bytes::{Buf, Bytes, BytesMut};
+
+use crate::error::NetworkError;
+
+/// Reconstructs message boundaries over a byte stream.
+///
+/// Stream transports such as TCP have no concept of a "message" — a single
+/// `read` can return part of one logical frame, several frames at once, or
+/// both. A `FrameDecoder` is fed raw bytes as they arrive and hands back
+/// zero or more complete frames, buffering whatever is left over internally.
+pub trait FrameDecoder: Send {
+    /// Wrap a payload so the peer's decoder can find its boundaries.
+    fn encode(&self, payload: &[u8]) -> Bytes;
+
+    /// Feed newly read bytes in and drain any complete frames accumulated so
+    /// far. Partial data is retained until the rest of the frame arrives.
+    fn decode(&mut self, incoming: &[u8]) -> Result<Vec<Bytes>, NetworkError>;
+}
+
+/// Passes bytes through untouched, one `NetworkRawPacket` per `read`. This is
+/// the historical behaviour, kept as the default so datagram protocols
+/// (UDP, QUIC datagrams) that already preserve message boundaries don't pay
+/// for framing they don't need.
+struct NoFraming;
+
+impl FrameDecoder for NoFraming {
+    fn encode(&self, payload: &[u8]) -> Bytes {
+        Bytes::copy_from_slice(payload)
+    }
+
+    fn decode(&mut self, incoming: &[u8]) -> Result<Vec<Bytes>, NetworkError> {
+        Ok(vec![Bytes::copy_from_slice(incoming)])
+    }
+}
+
+/// Prefixes each frame with a big-endian `u32` length.
+pub struct LengthPrefixed {
+    buffer: BytesMut,
+    max_packet_size: usize,
+}
+
+impl LengthPrefixed {
+    pub fn new(max_packet_size: usize) -> Self {
+        Self {
+            buffer: BytesMut::new(),
+            max_packet_size,
+        }
+    }
+}
+
+impl FrameDecoder for LengthPrefixed {
+    fn encode(&self, payload: &[u8]) -> Bytes {
+        let mut framed = BytesMut::with_capacity(4 + payload.len());
+        framed.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        framed.extend_from_slice(payload);
+        framed.freeze()
+    }
+
+    fn decode(&mut self, incoming: &[u8]) -> Result<Vec<Bytes>, NetworkError> {
+        self.buffer.extend_from_slice(incoming);
+        let mut frames = Vec::new();
+
+        loop {
+            if self.buffer.len() < 4 {
+                break;
+            }
+            let len = u32::from_be_bytes(self.buffer[..4].try_into().unwrap()) as usize;
+            if len > self.max_packet_size {
+                self.buffer.clear();
+                return Err(NetworkError::Error(format!(
+                    "frame of {len} bytes exceeds max_packet_size of {}",
+                    self.max_packet_size
+                )));
+            }
+            if self.buffer.len() < 4 + len {
+                break;
+            }
+            self.buffer.advance(4);
+            frames.push(self.buffer.split_to(len).freeze());
+        }
+
+        Ok(frames)
+    }
+}
+
+/// Which [`FrameDecoder`] a [`NetworkNode`](crate::component::NetworkNode) uses
+/// for stream-oriented protocols.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Framing {
+    /// No message boundaries are reconstructed; each `read` becomes one packet.
+    #[default]
+    None,
+    /// A `u32` big-endian length prefix precedes every frame.
+    LengthPrefixed,
+}
+
+impl Framing {
+    pub fn decoder(self, max_packet_size: usize) -> Box<dyn FrameDecoder> {
+        match self {
+            Framing::None => Box::new(NoFraming),
+            Framing::LengthPrefixed => Box::new(LengthPrefixed::new(max_packet_size)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_a_frame_split_across_reads() {
+        let mut decoder = LengthPrefixed::new(1024);
+        let framed = decoder.encode(b"hello world");
+
+        assert!(decoder.decode(&framed[..6]).unwrap().is_empty());
+        let frames = decoder.decode(&framed[6..]).unwrap();
+
+        assert_eq!(frames, vec![Bytes::from_static(b"hello world")]);
+    }
+
+    #[test]
+    fn decodes_multiple_frames_in_one_read() {
+        let mut decoder = LengthPrefixed::new(1024);
+        let mut incoming = BytesMut::new();
+        incoming.extend_from_slice(&decoder.encode(b"first"));
+        incoming.extend_from_slice(&decoder.encode(b"second"));
+
+        let frames = decoder.decode(&incoming).unwrap();
+
+        assert_eq!(
+            frames,
+            vec![Bytes::from_static(b"first"), Bytes::from_static(b"second")]
+        );
+    }
+
+    #[test]
+    fn rejects_a_frame_over_max_packet_size() {
+        let mut decoder = LengthPrefixed::new(4);
+        let framed = decoder.encode(b"too big");
+
+        assert!(decoder.decode(&framed).is_err());
+    }
+}
@@ -0,0 +1,376 @@
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy::tasks::IoTaskPool;
+use bytes::Bytes;
+use kanal::{AsyncReceiver, AsyncSender};
+use quinn::{ClientConfig, Connection, Endpoint, ServerConfig};
+
+use crate::error::NetworkError;
+use crate::network::{NetworkEvent, NetworkProtocol, NetworkRawPacket};
+use crate::network_manager::NetworkNode;
+use crate::AsyncChannel;
+
+pub struct QuicPlugin;
+
+impl Plugin for QuicPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (manage_quic_client, manage_quic_server, handle_new_connection),
+        );
+    }
+}
+
+/// Builds a self-signed `ServerConfig` so a `QuicServerNode` can accept
+/// connections without requiring the caller to supply certificates up front.
+fn self_signed_server_config() -> ServerConfig {
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+    let cert_der = cert.serialize_der().unwrap();
+    let priv_key = rustls::PrivateKey(cert.serialize_private_key_der());
+    let cert_chain = vec![rustls::Certificate(cert_der)];
+
+    ServerConfig::with_single_cert(cert_chain, priv_key).unwrap()
+}
+
+#[derive(Component)]
+pub struct QuicServerNode {
+    endpoint: Option<Endpoint>,
+    new_connections: AsyncChannel<Connection>,
+}
+
+impl QuicServerNode {
+    pub fn new(addrs: impl ToSocketAddrs) -> Self {
+        let socket = addrs.to_socket_addrs().unwrap().next().unwrap();
+        let endpoint = Endpoint::server(self_signed_server_config(), socket).unwrap();
+        debug!(
+            "Starting QUIC server on {:?}",
+            endpoint.local_addr().unwrap()
+        );
+
+        Self {
+            endpoint: Some(endpoint),
+            new_connections: AsyncChannel::new(),
+        }
+    }
+
+    pub fn start(&self, network_node: &mut NetworkNode) {
+        match self.endpoint.clone() {
+            None => network_node
+                .error_channel()
+                .sender
+                .send(NetworkError::Error("server not exist".to_string()))
+                .expect("Error channel has closed"),
+            Some(endpoint) => {
+                let new_connections_sender = self.new_connections.sender.clone_async();
+                IoTaskPool::get()
+                    .spawn(async move {
+                        while let Some(incoming) = endpoint.accept().await {
+                            match incoming.await {
+                                Ok(connection) => {
+                                    new_connections_sender.send(connection).await.unwrap();
+                                }
+                                Err(e) => error!("QUIC handshake failed: {:?}", e),
+                            }
+                        }
+                    })
+                    .detach();
+            }
+        }
+    }
+
+    pub async fn recv_loop(
+        connection: Connection,
+        message_sender: AsyncSender<NetworkRawPacket>,
+        error_sender: AsyncSender<NetworkError>,
+        cancel_flag: Arc<AtomicBool>,
+        max_packet_size: usize,
+    ) {
+        loop {
+            if cancel_flag.load(std::sync::atomic::Ordering::Relaxed) {
+                break;
+            }
+            // Accepting is cheap; the read itself is spawned onto its own
+            // task so one large/slow stream can't stall the others QUIC is
+            // meant to let run concurrently.
+            match connection.accept_uni().await {
+                Ok(mut recv_stream) => {
+                    let message_sender = message_sender.clone();
+                    let error_sender = error_sender.clone();
+                    let remote_address = connection.remote_address();
+                    IoTaskPool::get()
+                        .spawn(async move {
+                            match recv_stream.read_to_end(max_packet_size).await {
+                                Ok(bytes) => {
+                                    debug!(
+                                        "Received {} bytes from {}",
+                                        bytes.len(),
+                                        remote_address,
+                                    );
+                                    message_sender
+                                        .send(NetworkRawPacket {
+                                            socket: remote_address,
+                                            bytes: Bytes::from(bytes),
+                                        })
+                                        .await
+                                        .expect("Message channel has closed.");
+                                }
+                                Err(e) => {
+                                    error_sender
+                                        .send(NetworkError::Error(e.to_string()))
+                                        .await
+                                        .expect("Error channel has closed");
+                                }
+                            }
+                        })
+                        .detach();
+                }
+                Err(e) => {
+                    error!("Connection closed: {:?}", e);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct QuicClientNode {
+    socket: SocketAddr,
+    /// Skips server certificate verification entirely when `true`. Off by
+    /// default: QUIC's built-in TLS is only as trustworthy as the
+    /// verification behind it, so accepting any certificate must be an
+    /// explicit opt-in (e.g. connecting to a dev server with a self-signed
+    /// cert), never the only mode available.
+    insecure_skip_verify: bool,
+}
+
+impl QuicClientNode {
+    pub fn new(addrs: impl ToSocketAddrs) -> Self {
+        Self {
+            socket: addrs.to_socket_addrs().unwrap().next().unwrap(),
+            insecure_skip_verify: false,
+        }
+    }
+
+    /// Like [`new`](Self::new), but skips server certificate verification.
+    /// Only use this against a known, trusted peer (e.g. local development
+    /// with a self-signed certificate) — never over an untrusted network.
+    pub fn new_insecure(addrs: impl ToSocketAddrs) -> Self {
+        Self {
+            socket: addrs.to_socket_addrs().unwrap().next().unwrap(),
+            insecure_skip_verify: true,
+        }
+    }
+
+    fn client_crypto_config(&self) -> rustls::ClientConfig {
+        if self.insecure_skip_verify {
+            rustls::ClientConfig::builder()
+                .with_safe_defaults()
+                .with_custom_certificate_verifier(Arc::new(NoCertVerification))
+                .with_no_client_auth()
+        } else {
+            let mut roots = rustls::RootCertStore::empty();
+            roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+                rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
+                    ta.subject,
+                    ta.spki,
+                    ta.name_constraints,
+                )
+            }));
+            rustls::ClientConfig::builder()
+                .with_safe_defaults()
+                .with_root_certificates(roots)
+                .with_no_client_auth()
+        }
+    }
+
+    pub fn start(&self, net: &mut NetworkNode) {
+        let socket = self.socket;
+        let cancel_flag = net.cancel_flag.clone();
+        let graceful_flag = net.graceful_flag.clone();
+        let message_receiver = net.send_channel().receiver.clone_async();
+        let error_sender = net.error_channel().sender.clone_async();
+        let crypto_config = self.client_crypto_config();
+        IoTaskPool::get()
+            .spawn(async move {
+                let mut endpoint =
+                    Endpoint::client("0.0.0.0:0".parse::<SocketAddr>().unwrap()).unwrap();
+                endpoint.set_default_client_config(ClientConfig::new(Arc::new(crypto_config)));
+
+                match endpoint.connect(socket, "localhost") {
+                    Ok(connecting) => match connecting.await {
+                        Ok(connection) => {
+                            Self::send_loop(
+                                connection,
+                                message_receiver,
+                                error_sender.clone(),
+                                cancel_flag.clone(),
+                                graceful_flag,
+                            )
+                            .await;
+                        }
+                        Err(e) => error_sender
+                            .send(NetworkError::Error(e.to_string()))
+                            .await
+                            .expect("Error channel has closed"),
+                    },
+                    Err(e) => error_sender
+                        .send(NetworkError::Error(e.to_string()))
+                        .await
+                        .expect("Error channel has closed"),
+                }
+            })
+            .detach()
+    }
+
+    async fn send_loop(
+        connection: Connection,
+        message_receiver: AsyncReceiver<NetworkRawPacket>,
+        error_sender: AsyncSender<NetworkError>,
+        cancel_flag: Arc<AtomicBool>,
+        graceful_flag: Arc<AtomicBool>,
+    ) {
+        loop {
+            if cancel_flag.load(std::sync::atomic::Ordering::Relaxed) {
+                break;
+            }
+
+            if graceful_flag.load(std::sync::atomic::Ordering::Relaxed)
+                && message_receiver.len() == 0
+            {
+                debug!("graceful shutdown: send queue drained, closing");
+                break;
+            }
+
+            // Race the next queued message against a short poll interval so a
+            // graceful shutdown or hard cancel is noticed promptly even while
+            // idle, instead of blocking forever on `recv`.
+            let next_message = futures_lite::future::or(
+                async { message_receiver.recv().await.ok() },
+                async {
+                    async_io::Timer::after(std::time::Duration::from_millis(50)).await;
+                    None
+                },
+            )
+            .await;
+
+            if let Some(message) = next_message {
+                // Opening the stream and writing to it is spawned per
+                // message so a large payload can't block smaller ones
+                // queued right behind it on the same connection.
+                let connection = connection.clone();
+                let error_sender = error_sender.clone();
+                IoTaskPool::get()
+                    .spawn(async move {
+                        match connection.open_uni().await {
+                            Ok(mut send_stream) => {
+                                if let Err(e) = send_stream.write_all(&message.bytes).await {
+                                    error!("{:?}", e);
+                                    error_sender
+                                        .send(NetworkError::SendError)
+                                        .await
+                                        .expect("Error channel has closed")
+                                }
+                                let _ = send_stream.finish().await;
+                            }
+                            Err(e) => {
+                                error!("{:?}", e);
+                                error_sender
+                                    .send(NetworkError::SendError)
+                                    .await
+                                    .expect("Error channel has closed")
+                            }
+                        }
+                    })
+                    .detach();
+            }
+        }
+    }
+}
+
+/// Accepts any server certificate, bypassing the peer-authenticity half of
+/// QUIC's built-in TLS. Only ever constructed via
+/// [`QuicClientNode::new_insecure`] — real certificate verification
+/// (against the bundled `webpki_roots` root certificates) is the default.
+struct NoCertVerification;
+
+impl rustls::client::ServerCertVerifier for NoCertVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: std::time::SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::ServerCertVerified::assertion())
+    }
+}
+
+fn manage_quic_client(
+    mut commands: Commands,
+    mut q_quic_client: Query<(Entity, &QuicClientNode), Added<QuicClientNode>>,
+) {
+    for (e, quic_client) in q_quic_client.iter_mut() {
+        let mut net_node = NetworkNode::new(NetworkProtocol::QUIC, None, Some(quic_client.socket));
+        quic_client.start(&mut net_node);
+        commands.entity(e).insert(net_node);
+    }
+}
+
+fn manage_quic_server(
+    mut commands: Commands,
+    q_quic_server: Query<(Entity, &QuicServerNode), Added<QuicServerNode>>,
+) {
+    for (e, quic_server) in q_quic_server.iter() {
+        let mut net_node = NetworkNode::new(
+            NetworkProtocol::QUIC,
+            quic_server.endpoint.clone().unwrap().local_addr().ok(),
+            None,
+        );
+        quic_server.start(&mut net_node);
+        commands.entity(e).insert(net_node);
+    }
+}
+
+fn handle_new_connection(
+    mut commands: Commands,
+    mut q_quic_server: Query<(Entity, &mut QuicServerNode, &mut NetworkNode)>,
+    mut node_events: EventWriter<NetworkEvent>,
+) {
+    for (entity, quic_server, net_node) in q_quic_server.iter_mut() {
+        while let Ok(Some(connection)) = quic_server.new_connections.receiver.try_recv() {
+            debug!("new Quic client {:?} connected", connection.remote_address());
+            let cancel_flag = net_node.cancel_flag.clone();
+            let recv_sender = net_node.recv_channel().sender.clone_async();
+            let error_sender = net_node.error_channel().sender.clone_async();
+            let quic_client = commands
+                .spawn(NetworkNode::new(
+                    NetworkProtocol::QUIC,
+                    None,
+                    Some(connection.remote_address()),
+                ))
+                .id();
+            commands.entity(entity).push_children(&[quic_client]);
+
+            IoTaskPool::get()
+                .spawn(async move {
+                    QuicServerNode::recv_loop(
+                        connection,
+                        recv_sender,
+                        error_sender.clone(),
+                        cancel_flag.clone(),
+                        65_507,
+                    )
+                    .await;
+                })
+                .detach();
+
+            node_events.send(NetworkEvent::Connected(quic_client));
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::atomic::{AtomicU16, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::channel_kind::{ChannelInbox, ChannelKind};
+use crate::component::NetworkNode;
+use crate::error::NetworkError;
+
+const KIND_REQUEST: u8 = 0;
+const KIND_RESPONSE: u8 = 1;
+
+/// A handler registered against an RPC `path`, turning a request payload
+/// into a response payload.
+pub type RpcHandler = Arc<dyn Fn(Bytes) -> Bytes + Send + Sync>;
+
+/// Fired for every incoming RPC request, handled or not, so systems can
+/// observe traffic without going through the `path` registry.
+#[derive(Event, Debug, Clone)]
+pub struct RpcRequestEvent {
+    pub node: Entity,
+    pub request_id: u16,
+    pub path: String,
+    pub payload: Bytes,
+}
+
+pub struct RpcPlugin;
+
+impl Plugin for RpcPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RpcRequestEvent>()
+            .add_systems(
+                PreUpdate,
+                ensure_channel_inbox.before(crate::channel_kind::demux_recv_channel),
+            )
+            .add_systems(PostUpdate, (flush_rpc_outbox, dispatch_rpc_frames));
+    }
+}
+
+/// Inserts the [`ChannelInbox`] a fresh [`RpcNode`] needs to receive
+/// anything: without it, `demux_recv_channel` simply has nothing on this
+/// entity to route into, and RPC frames are silently dropped forever rather
+/// than erroring. Runs ahead of `demux_recv_channel` so the inbox is always
+/// present by the time a packet could arrive for it.
+fn ensure_channel_inbox(
+    mut commands: Commands,
+    q_new: Query<Entity, (Added<RpcNode>, Without<ChannelInbox>)>,
+) {
+    for entity in q_new.iter() {
+        commands.entity(entity).insert(ChannelInbox::default());
+    }
+}
+
+/// An outbound RPC frame waiting to be handed to `NetworkNode::send`,
+/// ordered so the send side can let latency-sensitive calls jump ahead of
+/// bulk traffic instead of going out in plain arrival order.
+struct PendingFrame {
+    priority: u8,
+    sequence: u64,
+    bytes: Bytes,
+}
+
+impl PartialEq for PendingFrame {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority && self.sequence == other.sequence
+    }
+}
+
+impl Eq for PendingFrame {}
+
+impl PartialOrd for PendingFrame {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PendingFrame {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        // Higher priority pops first; ties broken oldest-sequence-first so
+        // same-priority frames still go out FIFO.
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+/// Layers a request/response protocol on top of a [`NetworkNode`]'s raw
+/// packet channels. Outbound calls are tagged with a `request_id` so
+/// replies can be correlated back to the caller regardless of what else is
+/// in flight on the connection.
+///
+/// Requires a [`ChannelInbox`] on the same entity: [`RpcNode`] frames share
+/// the connection's `recv_message_channel` with other typed subsystems
+/// (streaming, typed codecs), so a single demux system routes them apart by
+/// a reserved leading [`ChannelKind`] byte instead of each subsystem racing
+/// to drain the raw channel itself.
+#[derive(Component, Default)]
+pub struct RpcNode {
+    pending: Arc<RwLock<HashMap<u16, kanal::Sender<Result<Bytes, NetworkError>>>>>,
+    next_request_id: AtomicU16,
+    handlers: Arc<RwLock<HashMap<String, RpcHandler>>>,
+    outbox: Arc<Mutex<BinaryHeap<PendingFrame>>>,
+    next_sequence: AtomicU64,
+}
+
+impl RpcNode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a handler invoked whenever a request for `path` arrives.
+    pub fn register(&self, path: impl Into<String>, handler: RpcHandler) {
+        self.handlers.write().unwrap().insert(path.into(), handler);
+    }
+
+    /// Issues a request and awaits the matching response, dropping the
+    /// pending entry if `timeout` elapses first. `priority` determines the
+    /// order queued requests are handed to the node's send channel in
+    /// (higher goes first), so a latency-sensitive call doesn't wait behind
+    /// bulk transfers queued ahead of it. The frame is queued on this node's
+    /// priority outbox and actually sent by [`flush_rpc_outbox`], not by
+    /// this call directly, so concurrent callers get reordered together.
+    pub async fn call(
+        &self,
+        path: &str,
+        priority: u8,
+        payload: Bytes,
+        timeout: Duration,
+    ) -> Result<Bytes, NetworkError> {
+        if path.len() > u8::MAX as usize {
+            return Err(NetworkError::Error("RPC path too long".to_string()));
+        }
+
+        let (tx, rx) = kanal::bounded(1);
+        let request_id = {
+            let mut pending = self.pending.write().unwrap();
+            if pending.len() >= u16::MAX as usize {
+                return Err(NetworkError::Error("no free RPC request ids".to_string()));
+            }
+            loop {
+                let id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
+                if !pending.contains_key(&id) {
+                    pending.insert(id, tx);
+                    break id;
+                }
+            }
+        };
+
+        let mut frame = BytesMut::with_capacity(2 + 1 + 1 + path.len() + payload.len());
+        frame.put_u8(ChannelKind::Rpc.to_byte());
+        frame.put_u8(KIND_REQUEST);
+        frame.put_u8(priority);
+        frame.put_u16(request_id);
+        frame.put_u8(path.len() as u8);
+        frame.put_slice(path.as_bytes());
+        frame.put_slice(&payload);
+
+        let sequence = self.next_sequence.fetch_add(1, Ordering::Relaxed);
+        self.outbox.lock().unwrap().push(PendingFrame {
+            priority,
+            sequence,
+            bytes: frame.freeze(),
+        });
+
+        let async_rx = rx.clone_async();
+        let recv_fut = async {
+            match async_rx.recv().await {
+                Ok(result) => result,
+                Err(_) => Err(NetworkError::Error("RPC sender dropped".to_string())),
+            }
+        };
+        let timeout_fut = async {
+            async_io::Timer::after(timeout).await;
+            Err(NetworkError::Error(format!(
+                "RPC call to {path} timed out after {timeout:?}"
+            )))
+        };
+
+        let result = futures_lite::future::or(recv_fut, timeout_fut).await;
+        self.pending.write().unwrap().remove(&request_id);
+        result
+    }
+
+    /// Drops every pending call, e.g. when the underlying connection closes.
+    pub fn cancel_pending(&self) {
+        self.pending.write().unwrap().clear();
+    }
+}
+
+/// Drains each node's priority outbox in order (highest `priority` first)
+/// and hands the frames to `NetworkNode::send`, so latency-sensitive calls
+/// queued this tick jump ahead of bulk ones queued earlier.
+fn flush_rpc_outbox(q_rpc: Query<(&RpcNode, &NetworkNode)>) {
+    for (rpc, net) in q_rpc.iter() {
+        let mut outbox = rpc.outbox.lock().unwrap();
+        while let Some(frame) = outbox.pop() {
+            net.send(&frame.bytes);
+        }
+    }
+}
+
+fn dispatch_rpc_frames(
+    q_rpc: Query<(Entity, &RpcNode, &NetworkNode, &ChannelInbox)>,
+    mut request_events: EventWriter<RpcRequestEvent>,
+) {
+    for (entity, rpc, net, inbox) in q_rpc.iter() {
+        // `stop_graceful` also flips `running` to false so no *new* calls
+        // get issued, but in-flight ones must still get their replies (or
+        // time out) instead of being wiped here — only a hard `stop()`
+        // (`cancel_flag`) means the connection is actually gone.
+        if net.cancel_flag.load(Ordering::Relaxed) {
+            rpc.cancel_pending();
+            continue;
+        }
+
+        while let Ok(Some(mut buf)) = inbox.rpc.receiver.try_recv() {
+            if !buf.has_remaining() {
+                continue;
+            }
+
+            match buf.get_u8() {
+                KIND_REQUEST if buf.remaining() >= 1 + 2 + 1 => {
+                    let _priority = buf.get_u8();
+                    let request_id = buf.get_u16();
+                    let path_len = buf.get_u8() as usize;
+                    if buf.remaining() < path_len {
+                        debug!("dropped RPC request with truncated path");
+                        continue;
+                    }
+                    let path = String::from_utf8_lossy(&buf[..path_len]).into_owned();
+                    buf.advance(path_len);
+                    let payload = buf;
+
+                    if let Some(handler) = rpc.handlers.read().unwrap().get(&path).cloned() {
+                        let response = handler(payload.clone());
+                        let mut frame = BytesMut::with_capacity(1 + 2 + response.len());
+                        frame.put_u8(ChannelKind::Rpc.to_byte());
+                        frame.put_u8(KIND_RESPONSE);
+                        frame.put_u16(request_id);
+                        frame.put_slice(&response);
+                        net.send(&frame);
+                    }
+
+                    request_events.send(RpcRequestEvent {
+                        node: entity,
+                        request_id,
+                        path,
+                        payload,
+                    });
+                }
+                KIND_RESPONSE if buf.remaining() >= 2 => {
+                    let request_id = buf.get_u16();
+                    let payload = buf;
+                    if let Some(tx) = rpc.pending.write().unwrap().remove(&request_id) {
+                        let _ = tx.try_send(Ok(payload));
+                    }
+                }
+                _ => debug!("dropped malformed RPC frame"),
+            }
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, RwLock};
+
+use bevy::prelude::*;
+use bevy::tasks::IoTaskPool;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use futures_lite::{Stream, StreamExt};
+
+use crate::channel_kind::{ChannelInbox, ChannelKind};
+use crate::component::NetworkNode;
+use crate::NetworkRawPacket;
+
+const KIND_CHUNK: u8 = 0;
+const KIND_END: u8 = 1;
+
+fn encode_chunk(stream_id: u32, payload: &[u8]) -> Bytes {
+    let mut frame = BytesMut::with_capacity(1 + 1 + 4 + payload.len());
+    frame.put_u8(ChannelKind::Stream.to_byte());
+    frame.put_u8(KIND_CHUNK);
+    frame.put_u32(stream_id);
+    frame.put_slice(payload);
+    frame.freeze()
+}
+
+fn encode_end(stream_id: u32) -> Bytes {
+    let mut frame = BytesMut::with_capacity(1 + 1 + 4);
+    frame.put_u8(ChannelKind::Stream.to_byte());
+    frame.put_u8(KIND_END);
+    frame.put_u32(stream_id);
+    frame.freeze()
+}
+
+/// Fired when the first chunk of a new incoming stream arrives. `chunks`
+/// yields each reassembled `Bytes` chunk in order and ends when the sender
+/// emits its end-of-stream marker or the connection drops.
+#[derive(Event)]
+pub struct NetworkStreamEvent {
+    pub node: Entity,
+    pub stream_id: u32,
+    pub chunks: kanal::AsyncReceiver<Bytes>,
+}
+
+pub struct NetworkStreamPlugin;
+
+impl Plugin for NetworkStreamPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<NetworkStreamEvent>()
+            .add_systems(
+                PreUpdate,
+                ensure_channel_inbox.before(crate::channel_kind::demux_recv_channel),
+            )
+            .add_systems(PostUpdate, dispatch_stream_frames);
+    }
+}
+
+/// Inserts the [`ChannelInbox`] a fresh [`StreamingNode`] needs to receive
+/// anything: without it, `demux_recv_channel` simply has nothing on this
+/// entity to route into, and stream frames are silently dropped forever
+/// rather than erroring. Runs ahead of `demux_recv_channel` so the inbox is
+/// always present by the time a packet could arrive for it.
+fn ensure_channel_inbox(
+    mut commands: Commands,
+    q_new: Query<Entity, (Added<StreamingNode>, Without<ChannelInbox>)>,
+) {
+    for entity in q_new.iter() {
+        commands.entity(entity).insert(ChannelInbox::default());
+    }
+}
+
+/// Layers chunked, multiplexed streaming on top of a [`NetworkNode`]'s raw
+/// packet channels, so large transfers (maps, save files, snapshots) don't
+/// have to be buffered whole in memory or block other traffic while they
+/// send.
+///
+/// Requires a [`ChannelInbox`] on the same entity: stream frames share the
+/// connection's `recv_message_channel` with other typed subsystems (RPC,
+/// typed codecs), so a single demux system routes them apart by a reserved
+/// leading [`ChannelKind`] byte instead of each subsystem racing to drain
+/// the raw channel itself.
+#[derive(Component, Default)]
+pub struct StreamingNode {
+    next_stream_id: AtomicU32,
+    incoming: Arc<RwLock<HashMap<u32, kanal::Sender<Bytes>>>>,
+}
+
+impl StreamingNode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends `chunks` as a new stream. Chunks are queued on `net`'s send
+    /// channel as soon as they're produced, so concurrent `send_stream`
+    /// calls (and any other traffic on the node) interleave on the wire
+    /// instead of a big transfer starving everything else.
+    pub fn send_stream(
+        &self,
+        net: &NetworkNode,
+        mut chunks: impl Stream<Item = Bytes> + Send + Unpin + 'static,
+    ) {
+        let stream_id = self.next_stream_id.fetch_add(1, Ordering::Relaxed);
+        let sender = net.send_channel().sender.clone_async();
+        let peer_addr = net.peer_addr;
+        IoTaskPool::get()
+            .spawn(async move {
+                while let Some(chunk) = chunks.next().await {
+                    sender
+                        .send(NetworkRawPacket {
+                            socket: peer_addr,
+                            bytes: encode_chunk(stream_id, &chunk),
+                        })
+                        .await
+                        .expect("Message channel has closed.");
+                }
+                sender
+                    .send(NetworkRawPacket {
+                        socket: peer_addr,
+                        bytes: encode_end(stream_id),
+                    })
+                    .await
+                    .expect("Message channel has closed.");
+            })
+            .detach();
+    }
+
+    /// Drops every in-progress incoming stream, signalling consumers via a
+    /// closed channel, e.g. when the underlying connection closes.
+    pub fn cancel_incoming(&self) {
+        self.incoming.write().unwrap().clear();
+    }
+}
+
+fn dispatch_stream_frames(
+    q_streams: Query<(Entity, &StreamingNode, &NetworkNode, &ChannelInbox)>,
+    mut stream_events: EventWriter<NetworkStreamEvent>,
+) {
+    for (entity, streaming, net, inbox) in q_streams.iter() {
+        // `stop_graceful` also flips `running` to false so no *new* streams
+        // get started, but in-flight ones must still reassemble (or the
+        // connection must actually be gone) — only a hard `stop()`
+        // (`cancel_flag`) or the peer going away (`disconnected_flag`)
+        // means that.
+        if net.cancel_flag.load(std::sync::atomic::Ordering::Relaxed)
+            || net.disconnected_flag.load(std::sync::atomic::Ordering::Relaxed)
+        {
+            streaming.cancel_incoming();
+            continue;
+        }
+
+        while let Ok(Some(mut buf)) = inbox.stream.receiver.try_recv() {
+            if buf.remaining() < 1 + 4 {
+                debug!("dropped malformed stream frame");
+                continue;
+            }
+
+            match buf.get_u8() {
+                KIND_CHUNK => {
+                    let stream_id = buf.get_u32();
+                    let payload = buf;
+
+                    let sender = {
+                        let mut incoming = streaming.incoming.write().unwrap();
+                        incoming
+                            .entry(stream_id)
+                            .or_insert_with(|| {
+                                let (tx, rx) = kanal::unbounded();
+                                stream_events.send(NetworkStreamEvent {
+                                    node: entity,
+                                    stream_id,
+                                    chunks: rx.clone_async(),
+                                });
+                                tx
+                            })
+                            .clone()
+                    };
+                    let _ = sender.try_send(payload);
+                }
+                KIND_END => {
+                    let stream_id = buf.get_u32();
+                    streaming.incoming.write().unwrap().remove(&stream_id);
+                }
+                _ => debug!("dropped unknown stream frame kind"),
+            }
+        }
+    }
+}
@@ -1,26 +1,139 @@
-use bevy::ecs::query::QueryData;
+use std::marker::PhantomData;
+
 use bevy::prelude::*;
-use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
+use crate::channel_kind::{ChannelInbox, ChannelKind};
 use crate::component::NetworkNode;
-use crate::component::TypedDecoder;
-use crate::prelude::NetworkMessage;
-use crate::prelude::StopMarker;
+use crate::error::NetworkError;
+
+/// A message type that can be sent and received as a strongly-typed network
+/// event rather than an opaque [`NetworkRawPacket`](crate::NetworkRawPacket).
+pub trait NetworkMessage: Event + Serialize + DeserializeOwned {
+    const NAME: &'static str;
+}
+
+/// Which `serde` backend a [`TypedDecoder`] uses on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SerdeBackend {
+    Bincode,
+    MessagePack,
+}
+
+impl SerdeBackend {
+    fn encode<T: Serialize>(self, value: &T) -> Result<Vec<u8>, NetworkError> {
+        match self {
+            SerdeBackend::Bincode => {
+                bincode::serialize(value).map_err(|e| NetworkError::Error(e.to_string()))
+            }
+            SerdeBackend::MessagePack => {
+                rmp_serde::to_vec(value).map_err(|e| NetworkError::Error(e.to_string()))
+            }
+        }
+    }
+
+    fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T, NetworkError> {
+        match self {
+            SerdeBackend::Bincode => {
+                bincode::deserialize(bytes).map_err(|e| NetworkError::Error(e.to_string()))
+            }
+            SerdeBackend::MessagePack => {
+                rmp_serde::from_slice(bytes).map_err(|e| NetworkError::Error(e.to_string()))
+            }
+        }
+    }
+}
+
+/// Marks a [`NetworkNode`] as decoding/encoding `T` with a specific
+/// [`SerdeBackend`], so the backend choice lives on the node and encode/decode
+/// can never drift apart.
+///
+/// Requires a [`ChannelInbox`] on the same entity: typed frames share the
+/// connection's `recv_message_channel` with other typed subsystems (RPC,
+/// streaming), so a single demux system routes them apart by a reserved
+/// leading [`ChannelKind`] byte instead of each subsystem racing to drain
+/// the raw channel itself.
+#[derive(Component)]
+pub struct TypedDecoder<T> {
+    pub backend: SerdeBackend,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> TypedDecoder<T> {
+    pub fn new(backend: SerdeBackend) -> Self {
+        Self {
+            backend,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: NetworkMessage> TypedDecoder<T> {
+    /// Serializes `message` with this decoder's backend and queues it on
+    /// `node`'s send channel.
+    pub fn send(&self, node: &NetworkNode, message: &T) -> Result<(), NetworkError> {
+        let encoded = self.backend.encode(message)?;
+        let mut bytes = Vec::with_capacity(1 + encoded.len());
+        bytes.push(ChannelKind::Typed.to_byte());
+        bytes.extend_from_slice(&encoded);
+        node.send(&bytes);
+        Ok(())
+    }
+}
 
 pub trait AppNetworkMessage {
+    /// Registers the decode pipeline for `T`: every node carrying a
+    /// `TypedDecoder<T>` has the typed lane of its [`ChannelInbox`] drained
+    /// (the packets `demux_recv_channel` routed there by their
+    /// [`ChannelKind::Typed`] byte), each frame deserialized with that node's
+    /// backend, and the result emitted as an
+    /// `Event<T>`. Decode failures go to the node's error channel instead of
+    /// panicking.
     fn register_decoder<T: NetworkMessage>(&mut self) -> &mut Self;
 }
 
 impl AppNetworkMessage for App {
     fn register_decoder<T: NetworkMessage>(&mut self) -> &mut Self {
         debug!("Registering decoder for {}", T::NAME);
-        self.add_systems(PostUpdate, decode_system::<T>);
+        self.add_event::<T>()
+            .add_systems(
+                PreUpdate,
+                ensure_channel_inbox::<T>.before(crate::channel_kind::demux_recv_channel),
+            )
+            .add_systems(PostUpdate, decode_system::<T>);
         self
     }
 }
 
-fn decode_system<T: for<'a> Deserialize<'a> + Send + Sync + 'static>(query: Query<(Entity, &NetworkNode), With<TypedDecoder<T>>>) {
-    for (entity, node) in query.iter() {
-        // debug!("Decoding entity {:?}", entity);
+/// Inserts the [`ChannelInbox`] a fresh `TypedDecoder<T>` needs to receive
+/// anything: without it, `demux_recv_channel` simply has nothing on this
+/// entity to route into, and typed frames are silently dropped forever
+/// rather than erroring. Runs ahead of `demux_recv_channel` so the inbox is
+/// always present by the time a packet could arrive for it.
+fn ensure_channel_inbox<T: NetworkMessage>(
+    mut commands: Commands,
+    q_new: Query<Entity, (Added<TypedDecoder<T>>, Without<ChannelInbox>)>,
+) {
+    for entity in q_new.iter() {
+        commands.entity(entity).insert(ChannelInbox::default());
+    }
+}
+
+fn decode_system<T: NetworkMessage>(
+    query: Query<(&NetworkNode, &TypedDecoder<T>, &ChannelInbox)>,
+    mut events: EventWriter<T>,
+) {
+    for (node, decoder, inbox) in query.iter() {
+        while let Ok(Some(bytes)) = inbox.typed.receiver.try_recv() {
+            match decoder.backend.decode::<T>(&bytes) {
+                Ok(message) => events.send(message),
+                Err(e) => node
+                    .error_channel()
+                    .sender
+                    .try_send(e)
+                    .expect("Error channel has closed"),
+            }
+        }
     }
 }
@@ -1,6 +1,7 @@
 use std::net::{SocketAddr, ToSocketAddrs};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_net::{TcpListener, TcpStream};
 use bevy::prelude::*;
@@ -10,6 +11,7 @@ use futures_lite::{AsyncReadExt, AsyncWriteExt, StreamExt};
 use kanal::{AsyncReceiver, AsyncSender};
 
 use crate::error::NetworkError;
+use crate::framing::Framing;
 use crate::network::{NetworkEvent, NetworkProtocol, NetworkRawPacket};
 use crate::network_manager::NetworkNode;
 use crate::AsyncChannel;
@@ -20,7 +22,12 @@ impl Plugin for TcpPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             PostUpdate,
-            (manage_tcp_client, manage_tcp_server, handle_new_connection),
+            (
+                manage_tcp_client,
+                manage_tcp_server,
+                handle_new_connection,
+                despawn_disconnected_connections,
+            ),
         );
     }
 }
@@ -29,6 +36,10 @@ impl Plugin for TcpPlugin {
 pub struct TcpServerNode {
     listener: Option<TcpListener>,
     new_connections: AsyncChannel<TcpStream>,
+    /// Live child connections are capped at this count; accepting pauses
+    /// once it's reached. Defaults to unbounded.
+    pub max_connections: usize,
+    paused: Arc<AtomicBool>,
 }
 
 impl TcpServerNode {
@@ -46,9 +57,28 @@ impl TcpServerNode {
         Self {
             listener: Some(listener),
             new_connections: AsyncChannel::new(),
+            max_connections: usize::MAX,
+            paused: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Stops the accept loop from taking on new connections until
+    /// [`resume`](Self::resume) is called. Sockets already queued by the OS
+    /// are left unaccepted rather than buffered in memory.
+    pub fn pause(&self) {
+        self.paused
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    pub fn resume(&self) {
+        self.paused
+            .store(false, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
     pub fn start(&self, network_node: &mut NetworkNode) {
         match self.listener.clone() {
             None => network_node
@@ -58,12 +88,36 @@ impl TcpServerNode {
                 .expect("Error channel has closed"),
             Some(listener) => {
                 let new_connections_sender = self.new_connections.sender.clone_async();
+                let cancel_flag = network_node.cancel_flag.clone();
+                let graceful_flag = network_node.graceful_flag.clone();
+                let paused = self.paused.clone();
                 IoTaskPool::get()
                     .spawn(async move {
                         let mut incoming = listener.incoming();
                         loop {
-                            while let Some(Ok(income)) = incoming.next().await {
-                                new_connections_sender.send(income).await.unwrap();
+                            if cancel_flag.load(std::sync::atomic::Ordering::Relaxed)
+                                || graceful_flag.load(std::sync::atomic::Ordering::Relaxed)
+                            {
+                                break;
+                            }
+                            if paused.load(std::sync::atomic::Ordering::Relaxed) {
+                                async_io::Timer::after(Duration::from_millis(50)).await;
+                                continue;
+                            }
+                            let accepted = futures_lite::future::or(
+                                async { incoming.next().await },
+                                async {
+                                    async_io::Timer::after(Duration::from_millis(50)).await;
+                                    None
+                                },
+                            )
+                            .await;
+                            match accepted {
+                                Some(Ok(income)) => {
+                                    new_connections_sender.send(income).await.unwrap();
+                                }
+                                Some(Err(e)) => error!("Failed to accept connection: {:?}", e),
+                                None => {}
                             }
                         }
                     })
@@ -77,9 +131,12 @@ impl TcpServerNode {
         message_sender: AsyncSender<NetworkRawPacket>,
         error_sender: AsyncSender<NetworkError>,
         cancel_flag: Arc<AtomicBool>,
+        disconnected_flag: Arc<AtomicBool>,
         max_packet_size: usize,
+        framing: Framing,
     ) {
         let mut buffer = vec![0; max_packet_size];
+        let mut decoder = framing.decoder(max_packet_size);
 
         loop {
             if cancel_flag.load(std::sync::atomic::Ordering::Relaxed) {
@@ -88,6 +145,7 @@ impl TcpServerNode {
             match stream.read(&mut buffer).await {
                 Ok(0) => {
                     error!("Connection closed by peer");
+                    disconnected_flag.store(true, std::sync::atomic::Ordering::Relaxed);
                     break;
                 }
                 Ok(n) => {
@@ -97,16 +155,28 @@ impl TcpServerNode {
                         n,
                         stream.local_addr().unwrap(),
                     );
-                    let bytes = Bytes::copy_from_slice(&buffer[..n]);
-                    message_sender
-                        .send(NetworkRawPacket {
-                            socket: stream.local_addr().unwrap(),
-                            bytes,
-                        })
-                        .await
-                        .expect("Message channel has closed.");
+                    let frames = match decoder.decode(&buffer[..n]) {
+                        Ok(frames) => frames,
+                        Err(e) => {
+                            error_sender
+                                .send(e)
+                                .await
+                                .expect("Error channel has closed");
+                            break;
+                        }
+                    };
+                    for bytes in frames {
+                        message_sender
+                            .send(NetworkRawPacket {
+                                socket: stream.local_addr().unwrap(),
+                                bytes,
+                            })
+                            .await
+                            .expect("Message channel has closed.");
+                    }
                 }
                 Err(e) => {
+                    disconnected_flag.store(true, std::sync::atomic::Ordering::Relaxed);
                     error_sender
                         .send(NetworkError::Error(e.to_string()))
                         .await
@@ -133,8 +203,11 @@ impl TcpClientNode {
     pub fn start(&self, net: &mut NetworkNode) {
         let socket = self.socket.clone();
         let cancel_flag = net.cancel_flag.clone();
+        let graceful_flag = net.graceful_flag.clone();
         let message_receiver = net.send_channel().receiver.clone_async();
         let error_sender = net.error_channel().sender.clone_async();
+        let framing = net.framing;
+        let max_packet_size = net.max_packet_size;
         IoTaskPool::get()
             .spawn(async move {
                 match TcpStream::connect(&socket).await {
@@ -144,6 +217,9 @@ impl TcpClientNode {
                             message_receiver,
                             error_sender.clone(),
                             cancel_flag.clone(),
+                            graceful_flag,
+                            framing,
+                            max_packet_size,
                         )
                         .await;
                     }
@@ -161,15 +237,40 @@ impl TcpClientNode {
         message_receiver: AsyncReceiver<NetworkRawPacket>,
         error_sender: AsyncSender<NetworkError>,
         cancel_flag: Arc<AtomicBool>,
+        graceful_flag: Arc<AtomicBool>,
+        framing: Framing,
+        max_packet_size: usize,
     ) {
+        let encoder = framing.decoder(max_packet_size);
         loop {
             if cancel_flag.load(std::sync::atomic::Ordering::Relaxed) {
                 break;
             }
 
-            while let Ok(message) = message_receiver.recv().await {
+            if graceful_flag.load(std::sync::atomic::Ordering::Relaxed)
+                && message_receiver.len() == 0
+            {
+                let _ = client.flush().await;
+                debug!("graceful shutdown: send queue drained, closing");
+                break;
+            }
+
+            // Race the next queued message against a short poll interval so a
+            // graceful shutdown or hard cancel is noticed promptly even while
+            // idle, instead of blocking forever on `recv`.
+            let next_message = futures_lite::future::or(
+                async { message_receiver.recv().await.ok() },
+                async {
+                    async_io::Timer::after(std::time::Duration::from_millis(50)).await;
+                    None
+                },
+            )
+            .await;
+
+            if let Some(message) = next_message {
                 debug!("send packet {:?}", message);
-                if let Err(e) = client.write_all(&message.bytes).await {
+                let framed = encoder.encode(&message.bytes);
+                if let Err(e) = client.write_all(&framed).await {
                     error!("{:?}", e);
                     error_sender
                         .send(NetworkError::SendError)
@@ -210,10 +311,35 @@ fn manage_tcp_server(
 
 fn handle_new_connection(
     mut commands: Commands,
-    mut q_tcp_server: Query<(Entity, &mut TcpServerNode, &mut NetworkNode)>,
+    mut q_tcp_server: Query<(Entity, &mut TcpServerNode, &mut NetworkNode, Option<&Children>)>,
+    mut cap_paused: Local<std::collections::HashSet<Entity>>,
     mut node_events: EventWriter<NetworkEvent>,
 ) {
-    for (entity, tcp_server, net_node) in q_tcp_server.iter_mut() {
+    for (entity, tcp_server, net_node, children) in q_tcp_server.iter_mut() {
+        let mut live_connections = children.map_or(0, |children| children.len());
+        // Resume once connections have drained to 90% of the cap, so we
+        // don't flap pause/resume right at the boundary. Only a pause made
+        // here for the cap is lifted here; a manual `pause()` stays until
+        // `resume()` is called.
+        let low_water = tcp_server
+            .max_connections
+            .saturating_sub(tcp_server.max_connections / 10);
+
+        if live_connections >= tcp_server.max_connections {
+            if !tcp_server.is_paused() {
+                tcp_server.pause();
+                cap_paused.insert(entity);
+                node_events.send(NetworkEvent::AcceptPaused(entity));
+            }
+            continue;
+        } else if live_connections <= low_water
+            && cap_paused.remove(&entity)
+            && tcp_server.is_paused()
+        {
+            tcp_server.resume();
+            node_events.send(NetworkEvent::AcceptResumed(entity));
+        }
+
         while let Ok(Some(tcp_stream)) = tcp_server.new_connections.receiver.try_recv() {
             debug!(
                 "new Tcp client {:?} connected",
@@ -222,13 +348,14 @@ fn handle_new_connection(
             let cancel_flag = net_node.cancel_flag.clone();
             let recv_sender = net_node.recv_channel().sender.clone_async();
             let error_sender = net_node.error_channel().sender.clone_async();
-            let tcp_client = commands
-                .spawn(NetworkNode::new(
-                    NetworkProtocol::TCP,
-                    None,
-                    tcp_stream.clone().peer_addr().ok(),
-                ))
-                .id();
+            let framing = net_node.framing;
+            let child_net_node = NetworkNode::new(
+                NetworkProtocol::TCP,
+                None,
+                tcp_stream.clone().peer_addr().ok(),
+            );
+            let disconnected_flag = child_net_node.disconnected_flag.clone();
+            let tcp_client = commands.spawn(child_net_node).id();
             commands.entity(entity).push_children(&[tcp_client]);
 
             IoTaskPool::get()
@@ -238,13 +365,38 @@ fn handle_new_connection(
                         recv_sender,
                         error_sender.clone(),
                         cancel_flag.clone(),
+                        disconnected_flag,
                         65_507,
+                        framing,
                     )
                     .await;
                 })
                 .detach();
 
             node_events.send(NetworkEvent::Connected(tcp_client));
+            live_connections += 1;
+            if live_connections >= tcp_server.max_connections {
+                break;
+            }
+        }
+    }
+}
+
+/// Despawns a server-side child connection once its `recv_loop` has marked
+/// it `disconnected_flag` (peer EOF or a read error). This is what keeps
+/// `handle_new_connection`'s `live_connections` count accurate over time —
+/// without it, connections would only ever accumulate and the server would
+/// pause accepting permanently once it first hit `max_connections`.
+fn despawn_disconnected_connections(
+    mut commands: Commands,
+    q_connections: Query<(Entity, &NetworkNode), With<Parent>>,
+) {
+    for (entity, net_node) in q_connections.iter() {
+        if net_node
+            .disconnected_flag
+            .load(std::sync::atomic::Ordering::Relaxed)
+        {
+            commands.entity(entity).despawn_recursive();
         }
     }
 }